@@ -0,0 +1,82 @@
+//! Newtype wrappers around the string IDs used to reference config
+//! definitions.
+//!
+//! A bare `type WareTypeId = String` lets any string stand in for any kind
+//! of ID, so a `Balance<WareTypeId>` could silently be keyed by a building
+//! ID instead, or two unrelated balances could be added together without a
+//! type error. Wrapping each ID family in its own type prevents that mix-up
+//! while keeping the on-disk TOML representation identical via
+//! `#[serde(transparent)]`.
+//!
+//! The core derives and impls here (`Display`, `From`, `Borrow<str>`, ...)
+//! are always available so the newtypes are as ergonomic as the raw strings
+//! they replace. Heavier integration impls, such as converting an ID into an
+//! arena-style numeric key, are gated behind the `full` feature so the core
+//! crate stays dependency-light.
+
+use std::borrow::Borrow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+macro_rules! string_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(pub String);
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(s: &str) -> Self {
+                Self(s.to_string())
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(s: String) -> Self {
+                Self(s)
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        #[cfg(feature = "full")]
+        impl $name {
+            /// A stable numeric key suitable for arena/slot-map style
+            /// indices, derived by hashing the underlying string ID.
+            pub fn arena_key(&self) -> u64 {
+                use std::hash::{Hash, Hasher};
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                self.0.hash(&mut hasher);
+                hasher.finish()
+            }
+        }
+    };
+}
+
+string_id!(
+    /// Identifies a [`WareType`](super::model::WareType).
+    WareTypeId
+);
+string_id!(
+    /// Identifies a [`BuildingType`](super::model::BuildingType).
+    BuildingTypeId
+);
+string_id!(
+    /// Identifies a [`PopulationClass`](super::model::PopulationClass).
+    PopulationClassId
+);
+string_id!(
+    /// Identifies a [`Recipe`](super::model::Recipe).
+    RecipeId
+);
@@ -0,0 +1,5 @@
+pub mod ids;
+pub mod model;
+pub mod plan;
+pub mod registry;
+pub mod state;
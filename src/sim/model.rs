@@ -6,34 +6,59 @@
 //! runtime. Similarly, structs may need to be converted to more efficient
 //! representations for simulation.
 
-use std::{collections::BTreeMap, ops::Add, time::Duration};
+use std::{
+    collections::BTreeMap,
+    ops::{Add, AddAssign, Mul, Neg, Sub},
+    time::Duration,
+};
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub use super::ids::{BuildingTypeId, PopulationClassId, RecipeId, WareTypeId};
+
+/// Shared, mostly-cosmetic fields embedded into every definition struct.
+///
+/// Flattened into each struct's TOML representation, so a config that only
+/// sets `display_name` keeps deserializing unchanged while authors can add
+/// a `description` or `tags` without touching existing definitions.
+/// `display_name` itself defaults to empty so structs that didn't carry one
+/// before this was introduced, such as `Recipe`, don't suddenly require it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Metadata {
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
 
 /// A ware that is produced and consumed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WareType {
-    pub display_name: String,
+    #[serde(flatten)]
+    pub metadata: Metadata,
 }
-pub type WareTypeId = String;
 
 /// A type of building.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildingType {
-    pub display_name: String,
+    #[serde(flatten)]
+    pub metadata: Metadata,
 }
-pub type BuildingTypeId = String;
 
 /// A type of building.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PopulationClass {
-    pub display_name: String,
+    #[serde(flatten)]
+    pub metadata: Metadata,
 }
-pub type PopulationClassId = String;
 
 /// Recipes are processes that convert input wares into output wares.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Recipe {
+    #[serde(flatten)]
+    pub metadata: Metadata,
     /// The building this recipe is performed in.
     pub building: BuildingTypeId,
     /// The input and output ware amounts, with inputs represented by negative
@@ -45,11 +70,43 @@ pub struct Recipe {
     /// labor costs. Labor surplus is not anticipated, but may be possible.
     pub labor: Balance<PopulationClassId>,
 }
-pub type RecipeId = String;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+impl Recipe {
+    /// The number of full cycles of this recipe that `inventory` has enough
+    /// input wares to run.
+    ///
+    /// For each ware `k` this recipe consumes (a negative amount `c_k` in
+    /// `conversion`), the limit is `floor(inventory[k] / -c_k)`; the result
+    /// is the minimum of that limit over every consumed ware, or `0` if the
+    /// recipe consumes no wares or any required input is entirely absent
+    /// from `inventory`.
+    pub fn max_cycles(&self, inventory: &Balance<WareTypeId>) -> u64 {
+        let mut cycles: Option<u64> = None;
+        for (ware, amount) in self.conversion.amounts.iter() {
+            if *amount >= 0 {
+                continue;
+            }
+            let available = inventory.get(ware).max(0) as u64;
+            let per_cycle = (-amount) as u64;
+            let max_for_ware = available / per_cycle;
+            cycles = Some(cycles.map_or(max_for_ware, |c| c.min(max_for_ware)));
+        }
+        cycles.unwrap_or(0)
+    }
+
+    /// The inventory that results from running this recipe `n` times.
+    pub fn apply(&self, inventory: &Balance<WareTypeId>, n: u64) -> Balance<WareTypeId> {
+        inventory + &(&self.conversion * (n as i64))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "K: Ord + Serialize",
+    deserialize = "K: Ord + DeserializeOwned"
+))]
 pub struct Balance<K> {
-    amounts: BTreeMap<K, i64>,
+    pub(crate) amounts: BTreeMap<K, i64>,
 }
 
 impl<K> Balance<K> {
@@ -60,6 +117,32 @@ impl<K> Balance<K> {
     }
 }
 
+impl<K> Default for Balance<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> Balance<K> {
+    /// The amount on hand for `key`, or `0` if it has no entry.
+    pub fn get(&self, key: &K) -> i64
+    where
+        K: Ord,
+    {
+        self.amounts.get(key).copied().unwrap_or(0)
+    }
+
+    /// Iterates over every entry with a recorded amount.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &i64)> {
+        self.amounts.iter()
+    }
+
+    /// Whether every recorded amount is non-negative.
+    pub fn is_non_negative(&self) -> bool {
+        self.amounts.values().all(|amount| *amount >= 0)
+    }
+}
+
 impl<K> Add for &Balance<K>
 where
     K: Clone + Ord,
@@ -68,10 +151,141 @@ where
 
     fn add(self, rhs: Self) -> Self::Output {
         let mut out = self.clone();
+        out += rhs;
+        out
+    }
+}
+
+impl<K> AddAssign<&Balance<K>> for Balance<K>
+where
+    K: Clone + Ord,
+{
+    fn add_assign(&mut self, rhs: &Balance<K>) {
         for (key, rhs_value) in rhs.amounts.iter() {
-            let lhs_value = out.amounts.entry(key.clone()).or_insert(0);
+            let lhs_value = self.amounts.entry(key.clone()).or_insert(0);
             *lhs_value += rhs_value;
         }
+    }
+}
+
+impl<K> Sub for &Balance<K>
+where
+    K: Clone + Ord,
+{
+    type Output = Balance<K>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut out = self.clone();
+        for (key, rhs_value) in rhs.amounts.iter() {
+            let lhs_value = out.amounts.entry(key.clone()).or_insert(0);
+            *lhs_value -= rhs_value;
+        }
         out
     }
 }
+
+impl<K> Neg for &Balance<K>
+where
+    K: Clone + Ord,
+{
+    type Output = Balance<K>;
+
+    fn neg(self) -> Self::Output {
+        Balance {
+            amounts: self.amounts.iter().map(|(k, v)| (k.clone(), -v)).collect(),
+        }
+    }
+}
+
+impl<K> Mul<i64> for &Balance<K>
+where
+    K: Clone + Ord,
+{
+    type Output = Balance<K>;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        Balance {
+            amounts: self
+                .amounts
+                .iter()
+                .map(|(k, v)| (k.clone(), v * rhs))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(entries: &[(&str, i64)]) -> Balance<WareTypeId> {
+        Balance {
+            amounts: entries
+                .iter()
+                .map(|(k, v)| (WareTypeId::from(*k), *v))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn mul_scales_every_amount_including_negative() {
+        let b = balance(&[("wood", -2), ("plank", 1)]);
+        let scaled = &b * 3;
+        assert_eq!(scaled.get(&"wood".into()), -6);
+        assert_eq!(scaled.get(&"plank".into()), 3);
+    }
+
+    #[test]
+    fn sub_and_neg_are_consistent_with_add() {
+        let a = balance(&[("wood", 5)]);
+        let b = balance(&[("wood", 2)]);
+        assert_eq!((&a - &b).get(&"wood".into()), 3);
+        assert_eq!((-&b).get(&"wood".into()), -2);
+        assert_eq!((&a + &-&b), &a - &b);
+    }
+
+    #[test]
+    fn add_assign_accumulates_in_place() {
+        let mut a = balance(&[("wood", 1)]);
+        a += &balance(&[("wood", 2), ("plank", 5)]);
+        assert_eq!(a.get(&"wood".into()), 3);
+        assert_eq!(a.get(&"plank".into()), 5);
+    }
+
+    #[test]
+    fn is_non_negative_detects_any_negative_entry() {
+        assert!(balance(&[("wood", 0), ("plank", 4)]).is_non_negative());
+        assert!(!balance(&[("wood", -1)]).is_non_negative());
+    }
+
+    fn sawmill() -> Recipe {
+        Recipe {
+            metadata: Metadata::default(),
+            building: BuildingTypeId::from("sawmill"),
+            conversion: balance(&[("wood", -2), ("plank", 1)]),
+            cycle: Duration::from_secs(1),
+            labor: Balance::new(),
+        }
+    }
+
+    #[test]
+    fn max_cycles_is_limited_by_the_scarcest_input() {
+        let recipe = sawmill();
+        assert_eq!(recipe.max_cycles(&balance(&[("wood", 5)])), 2);
+        assert_eq!(recipe.max_cycles(&balance(&[("wood", 0)])), 0);
+    }
+
+    #[test]
+    fn max_cycles_is_zero_when_a_required_input_is_absent() {
+        let recipe = sawmill();
+        assert_eq!(recipe.max_cycles(&Balance::new()), 0);
+    }
+
+    #[test]
+    fn apply_adds_scaled_conversion_to_inventory() {
+        let recipe = sawmill();
+        let result = recipe.apply(&balance(&[("wood", 5)]), 2);
+        assert_eq!(result.get(&"wood".into()), 1);
+        assert_eq!(result.get(&"plank".into()), 2);
+    }
+}
@@ -0,0 +1,336 @@
+//! Leontief-style production planning.
+//!
+//! Given a target final demand for wares and the set of known recipes,
+//! [`plan`] answers "what does it take to produce this": how many cycles of
+//! each recipe must run, what raw materials are left over as residual
+//! demand, and how much labor the whole plan costs.
+//!
+//! Demand is expanded recursively: a ware's demand is satisfied by picking a
+//! producing recipe, scaling it up to whole cycles, and turning its other
+//! inputs into new demand. Recipes producing more than one ware don't
+//! recurse back on themselves, but they can form dependency cycles through
+//! shared raw materials; those are caught with a visited set tracking the
+//! current recursion stack, and demand for anything still open on that stack
+//! (or explicitly marked as a primary ware) is left as residual instead of
+//! expanded further.
+//!
+//! Activating a recipe to meet demand for one of its outputs also produces
+//! its other outputs (and any surplus of the targeted one from rounding up
+//! to whole cycles) as a side effect. That surplus is tracked and netted
+//! against demand for the same ware encountered later, so a joint-production
+//! recipe whose outputs are each independently demanded isn't activated once
+//! per demanded output.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::model::{Balance, PopulationClassId, Recipe, RecipeId, WareTypeId};
+
+/// How to pick a recipe when more than one can produce a demanded ware.
+#[derive(Debug, Clone)]
+pub enum RecipeSelector {
+    /// Prefer whichever candidate recipe has the lowest total labor cost.
+    LowestLaborCost,
+    /// Prefer candidates in this order; the first one present in the
+    /// candidate set wins, falling back to an arbitrary candidate if none
+    /// of the listed recipes apply.
+    Priority(Vec<RecipeId>),
+}
+
+/// Options controlling how [`plan`] expands demand.
+#[derive(Debug, Clone)]
+pub struct PlanOptions {
+    /// How to choose between recipes that produce the same ware.
+    pub selector: RecipeSelector,
+    /// Wares treated as externally supplied raw materials: demand for them
+    /// is left as residual rather than expanded, even if a recipe exists
+    /// that could produce them.
+    pub primary_wares: BTreeSet<WareTypeId>,
+}
+
+impl Default for PlanOptions {
+    fn default() -> Self {
+        Self {
+            selector: RecipeSelector::LowestLaborCost,
+            primary_wares: BTreeSet::new(),
+        }
+    }
+}
+
+/// The result of expanding a target demand into recipe activations.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// How many cycles of each recipe must run.
+    pub activations: BTreeMap<RecipeId, u64>,
+    /// Demand left unsatisfied: primary wares, cycle-broken wares, and
+    /// wares with no known producer.
+    pub residual_demand: Balance<WareTypeId>,
+    /// Total labor required across every activated recipe.
+    pub labor_demand: Balance<PopulationClassId>,
+}
+
+/// Expands `demand` into the recipe activations needed to satisfy it.
+pub fn plan(
+    demand: &Balance<WareTypeId>,
+    recipes: &BTreeMap<RecipeId, Recipe>,
+    options: &PlanOptions,
+) -> Plan {
+    let producers = build_producer_map(recipes);
+
+    let mut activations: BTreeMap<RecipeId, u64> = BTreeMap::new();
+    let mut residual_demand: Balance<WareTypeId> = Balance::new();
+    let mut stack: BTreeSet<WareTypeId> = BTreeSet::new();
+    let mut produced: BTreeMap<WareTypeId, i64> = BTreeMap::new();
+
+    for (ware, amount) in demand.iter() {
+        if *amount > 0 {
+            expand(
+                ware,
+                *amount,
+                recipes,
+                &producers,
+                options,
+                &mut stack,
+                &mut produced,
+                &mut activations,
+                &mut residual_demand,
+            );
+        }
+    }
+
+    let labor_demand = activations
+        .iter()
+        .fold(Balance::new(), |mut acc, (recipe_id, cycles)| {
+            acc += &(&recipes[recipe_id].labor * (*cycles as i64));
+            acc
+        });
+
+    Plan {
+        activations,
+        residual_demand,
+        labor_demand,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn expand(
+    ware: &WareTypeId,
+    quantity: i64,
+    recipes: &BTreeMap<RecipeId, Recipe>,
+    producers: &BTreeMap<WareTypeId, Vec<RecipeId>>,
+    options: &PlanOptions,
+    stack: &mut BTreeSet<WareTypeId>,
+    produced: &mut BTreeMap<WareTypeId, i64>,
+    activations: &mut BTreeMap<RecipeId, u64>,
+    residual_demand: &mut Balance<WareTypeId>,
+) {
+    if quantity <= 0 {
+        return;
+    }
+
+    // Net against any surplus left over from an earlier activation that
+    // produced this ware as a side effect, so a joint-production recipe
+    // isn't activated again for demand its prior run already covers.
+    let surplus = produced.entry(ware.clone()).or_insert(0);
+    let from_surplus = (*surplus).min(quantity);
+    *surplus -= from_surplus;
+    let quantity = quantity - from_surplus;
+    if quantity <= 0 {
+        return;
+    }
+
+    if options.primary_wares.contains(ware) || stack.contains(ware) {
+        *residual_demand.amounts.entry(ware.clone()).or_insert(0) += quantity;
+        return;
+    }
+
+    let candidates = match producers.get(ware) {
+        Some(candidates) if !candidates.is_empty() => candidates,
+        _ => {
+            *residual_demand.amounts.entry(ware.clone()).or_insert(0) += quantity;
+            return;
+        }
+    };
+
+    let recipe_id = select_recipe(candidates, recipes, &options.selector);
+    let recipe = &recipes[&recipe_id];
+    let output_per_cycle = recipe.conversion.get(ware);
+    let cycles = (quantity as u64).div_ceil(output_per_cycle as u64);
+    *activations.entry(recipe_id.clone()).or_insert(0) += cycles;
+
+    // Any output beyond what was needed, including this activation's other
+    // outputs, becomes surplus available to net against later demand.
+    let produced_for_ware = output_per_cycle * cycles as i64 - quantity;
+    if produced_for_ware > 0 {
+        *produced.entry(ware.clone()).or_insert(0) += produced_for_ware;
+    }
+
+    stack.insert(ware.clone());
+    for (other_ware, amount) in recipe.conversion.iter() {
+        if other_ware == ware {
+            continue;
+        }
+        let delta = amount * cycles as i64;
+        if delta < 0 {
+            expand(
+                other_ware,
+                -delta,
+                recipes,
+                producers,
+                options,
+                stack,
+                produced,
+                activations,
+                residual_demand,
+            );
+        } else if delta > 0 {
+            *produced.entry(other_ware.clone()).or_insert(0) += delta;
+        }
+    }
+    stack.remove(ware);
+}
+
+fn build_producer_map(recipes: &BTreeMap<RecipeId, Recipe>) -> BTreeMap<WareTypeId, Vec<RecipeId>> {
+    let mut producers: BTreeMap<WareTypeId, Vec<RecipeId>> = BTreeMap::new();
+    for (recipe_id, recipe) in recipes {
+        for (ware, amount) in recipe.conversion.iter() {
+            if *amount > 0 {
+                producers
+                    .entry(ware.clone())
+                    .or_default()
+                    .push(recipe_id.clone());
+            }
+        }
+    }
+    producers
+}
+
+fn select_recipe(
+    candidates: &[RecipeId],
+    recipes: &BTreeMap<RecipeId, Recipe>,
+    selector: &RecipeSelector,
+) -> RecipeId {
+    match selector {
+        RecipeSelector::LowestLaborCost => candidates
+            .iter()
+            .min_by_key(|recipe_id| labor_cost(&recipes[*recipe_id]))
+            .cloned()
+            .expect("candidates is non-empty"),
+        RecipeSelector::Priority(order) => order
+            .iter()
+            .find(|recipe_id| candidates.contains(recipe_id))
+            .cloned()
+            .unwrap_or_else(|| candidates[0].clone()),
+    }
+}
+
+/// The total labor cost of a recipe, i.e. the negation of its labor balance
+/// (which records costs as negative amounts).
+fn labor_cost(recipe: &Recipe) -> i64 {
+    recipe.labor.iter().map(|(_, amount)| -amount).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::sim::model::{BuildingTypeId, Metadata};
+
+    fn balance(entries: &[(&str, i64)]) -> Balance<WareTypeId> {
+        Balance {
+            amounts: entries
+                .iter()
+                .map(|(k, v)| (WareTypeId::from(*k), *v))
+                .collect(),
+        }
+    }
+
+    fn recipe(conversion: &[(&str, i64)], labor: i64) -> Recipe {
+        Recipe {
+            metadata: Metadata::default(),
+            building: BuildingTypeId::from("building"),
+            conversion: balance(conversion),
+            cycle: Duration::from_secs(1),
+            labor: Balance {
+                amounts: [(PopulationClassId::from("worker"), -labor)].into(),
+            },
+        }
+    }
+
+    #[test]
+    fn absent_input_ware_becomes_residual_demand() {
+        let recipes = BTreeMap::from([(
+            RecipeId::from("smelt"),
+            recipe(&[("ore", -2), ("ingot", 1)], 1),
+        )]);
+        let demand = balance(&[("ingot", 3)]);
+
+        let result = plan(&demand, &recipes, &PlanOptions::default());
+
+        assert_eq!(result.activations[&RecipeId::from("smelt")], 3);
+        assert_eq!(result.residual_demand.get(&WareTypeId::from("ore")), 6);
+    }
+
+    #[test]
+    fn demand_cycle_among_non_primary_wares_terminates() {
+        let recipes = BTreeMap::from([
+            (RecipeId::from("a_to_b"), recipe(&[("a", -1), ("b", 1)], 1)),
+            (RecipeId::from("b_to_a"), recipe(&[("b", -1), ("a", 1)], 1)),
+        ]);
+        let demand = balance(&[("b", 5)]);
+
+        let result = plan(&demand, &recipes, &PlanOptions::default());
+
+        assert_eq!(result.activations[&RecipeId::from("a_to_b")], 5);
+        assert_eq!(result.activations[&RecipeId::from("b_to_a")], 5);
+        assert_eq!(result.residual_demand.get(&WareTypeId::from("b")), 5);
+    }
+
+    #[test]
+    fn priority_selector_falls_back_when_no_listed_recipe_applies() {
+        let recipes = BTreeMap::from([
+            (RecipeId::from("cheap"), recipe(&[("widget", 1)], 1)),
+            (RecipeId::from("pricey"), recipe(&[("widget", 1)], 10)),
+        ]);
+        let options = PlanOptions {
+            selector: RecipeSelector::Priority(vec![RecipeId::from("unrelated")]),
+            primary_wares: BTreeSet::new(),
+        };
+
+        let result = plan(&balance(&[("widget", 1)]), &recipes, &options);
+
+        assert_eq!(result.activations[&RecipeId::from("cheap")], 1);
+        assert!(!result.activations.contains_key(&RecipeId::from("pricey")));
+    }
+
+    #[test]
+    fn joint_outputs_demanded_independently_share_one_activation() {
+        let recipes = BTreeMap::from([(
+            RecipeId::from("coke_oven"),
+            recipe(&[("coal", -2), ("charcoal", 1), ("ash", 1)], 1),
+        )]);
+        let demand = balance(&[("charcoal", 10), ("ash", 10)]);
+
+        let result = plan(&demand, &recipes, &PlanOptions::default());
+
+        assert_eq!(result.activations[&RecipeId::from("coke_oven")], 10);
+        assert_eq!(result.residual_demand.get(&WareTypeId::from("coal")), 20);
+    }
+
+    #[test]
+    fn lowest_labor_cost_selector_prefers_cheaper_recipe() {
+        let recipes = BTreeMap::from([
+            (RecipeId::from("pricey"), recipe(&[("widget", 1)], 10)),
+            (RecipeId::from("cheap"), recipe(&[("widget", 1)], 1)),
+        ]);
+
+        let result = plan(
+            &balance(&[("widget", 1)]),
+            &recipes,
+            &PlanOptions::default(),
+        );
+
+        assert_eq!(result.activations[&RecipeId::from("cheap")], 1);
+        assert!(!result.activations.contains_key(&RecipeId::from("pricey")));
+    }
+}
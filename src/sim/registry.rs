@@ -0,0 +1,462 @@
+//! Referential-integrity checking and interning for simulation config.
+//!
+//! The structs in [`super::model`] reference each other by plain string ID,
+//! as noted in that module's docs. Before those definitions can drive a
+//! simulation, the references need to be checked for dangling targets and
+//! resolved to dense indices so hot simulation code isn't hashing and
+//! comparing strings on every tick. [`Registry`] does both: it validates an
+//! entire config set in one pass and, on success, exposes an interned,
+//! index-based view alongside the original string-keyed maps so callers can
+//! still serialize back to the config form.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use super::model::{
+    BuildingType, BuildingTypeId, PopulationClass, PopulationClassId, Recipe, RecipeId, WareType,
+    WareTypeId,
+};
+
+/// Dense index into one of a [`Registry`]'s interned tables.
+pub type Index = u32;
+
+/// A single dangling reference found while validating a [`Registry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DanglingReference {
+    /// A recipe names a building that has no definition.
+    Building {
+        recipe: RecipeId,
+        building: BuildingTypeId,
+    },
+    /// A recipe's conversion balance references a ware that has no
+    /// definition.
+    Ware { recipe: RecipeId, ware: WareTypeId },
+    /// A recipe's labor balance references a population class that has no
+    /// definition.
+    PopulationClass {
+        recipe: RecipeId,
+        population_class: PopulationClassId,
+    },
+}
+
+impl fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DanglingReference::Building { recipe, building } => {
+                write!(f, "recipe {recipe} names unknown building {building}")
+            }
+            DanglingReference::Ware { recipe, ware } => {
+                write!(
+                    f,
+                    "recipe {recipe} conversion references unknown ware {ware}"
+                )
+            }
+            DanglingReference::PopulationClass {
+                recipe,
+                population_class,
+            } => write!(
+                f,
+                "recipe {recipe} labor references unknown population class {population_class}"
+            ),
+        }
+    }
+}
+
+/// All dangling references found while validating a config set.
+///
+/// Validation does not stop at the first problem: every recipe is checked,
+/// so config authors can fix every offending reference in one pass instead
+/// of discovering them one at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub dangling: Vec<DanglingReference>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} dangling reference(s) found:", self.dangling.len())?;
+        for reference in &self.dangling {
+            writeln!(f, "  - {reference}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A [`Recipe`] with its string references resolved to dense indices into a
+/// [`Registry`]'s interned tables.
+#[derive(Debug, Clone)]
+pub struct ResolvedRecipe {
+    pub building: Index,
+    pub conversion: BTreeMap<Index, i64>,
+    pub cycle: Duration,
+    pub labor: BTreeMap<Index, i64>,
+}
+
+/// Bidirectional lookup between a config's string IDs and the dense indices
+/// a [`Registry`] interns them into.
+#[derive(Debug, Clone, Default)]
+struct InternTable<Id: Ord + Clone> {
+    by_index: Vec<Id>,
+    by_id: BTreeMap<Id, Index>,
+}
+
+impl<Id: Ord + Clone> InternTable<Id> {
+    fn from_keys<V>(map: &BTreeMap<Id, V>) -> Self {
+        let by_index: Vec<Id> = map.keys().cloned().collect();
+        let by_id = by_index
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, id)| (id, i as Index))
+            .collect();
+        Self { by_index, by_id }
+    }
+
+    fn index_of(&self, id: &Id) -> Option<Index> {
+        self.by_id.get(id).copied()
+    }
+
+    fn id_of(&self, index: Index) -> Option<&Id> {
+        self.by_index.get(index as usize)
+    }
+}
+
+/// A checked, simulation-ready view over a set of config definitions.
+///
+/// Construct with [`Registry::build`], which validates every recipe's
+/// references against the known wares, buildings, and population classes,
+/// collecting every dangling reference rather than stopping at the first.
+/// On success, each string ID is interned into a dense `u32` index and every
+/// recipe is resolved into a [`ResolvedRecipe`], while the original
+/// string-keyed maps are retained for lookups and round-trip serialization.
+#[derive(Debug, Clone)]
+pub struct Registry {
+    wares: BTreeMap<WareTypeId, WareType>,
+    buildings: BTreeMap<BuildingTypeId, BuildingType>,
+    population_classes: BTreeMap<PopulationClassId, PopulationClass>,
+    recipes: BTreeMap<RecipeId, Recipe>,
+
+    ware_index: InternTable<WareTypeId>,
+    building_index: InternTable<BuildingTypeId>,
+    population_class_index: InternTable<PopulationClassId>,
+    recipe_index: InternTable<RecipeId>,
+
+    resolved_recipes: Vec<ResolvedRecipe>,
+}
+
+impl Registry {
+    /// Validates and interns a full set of config definitions.
+    ///
+    /// Returns a [`ValidationError`] enumerating every dangling reference
+    /// found: a recipe's `building` that names no [`BuildingType`], or a key
+    /// in its `conversion`/`labor` balance that names no [`WareType`] or
+    /// [`PopulationClass`] respectively.
+    pub fn build(
+        wares: BTreeMap<WareTypeId, WareType>,
+        buildings: BTreeMap<BuildingTypeId, BuildingType>,
+        population_classes: BTreeMap<PopulationClassId, PopulationClass>,
+        recipes: BTreeMap<RecipeId, Recipe>,
+    ) -> Result<Self, ValidationError> {
+        let mut dangling = Vec::new();
+        for (recipe_id, recipe) in &recipes {
+            if !buildings.contains_key(&recipe.building) {
+                dangling.push(DanglingReference::Building {
+                    recipe: recipe_id.clone(),
+                    building: recipe.building.clone(),
+                });
+            }
+            for (ware, _) in recipe.conversion.amounts.iter() {
+                if !wares.contains_key(ware) {
+                    dangling.push(DanglingReference::Ware {
+                        recipe: recipe_id.clone(),
+                        ware: ware.clone(),
+                    });
+                }
+            }
+            for (population_class, _) in recipe.labor.amounts.iter() {
+                if !population_classes.contains_key(population_class) {
+                    dangling.push(DanglingReference::PopulationClass {
+                        recipe: recipe_id.clone(),
+                        population_class: population_class.clone(),
+                    });
+                }
+            }
+        }
+        if !dangling.is_empty() {
+            return Err(ValidationError { dangling });
+        }
+
+        let ware_index = InternTable::from_keys(&wares);
+        let building_index = InternTable::from_keys(&buildings);
+        let population_class_index = InternTable::from_keys(&population_classes);
+        let recipe_index = InternTable::from_keys(&recipes);
+
+        let resolved_recipes = recipe_index
+            .by_index
+            .iter()
+            .map(|recipe_id| {
+                let recipe = &recipes[recipe_id];
+                ResolvedRecipe {
+                    building: building_index
+                        .index_of(&recipe.building)
+                        .expect("building reference validated above"),
+                    conversion: recipe
+                        .conversion
+                        .amounts
+                        .iter()
+                        .map(|(k, v)| {
+                            (
+                                ware_index
+                                    .index_of(k)
+                                    .expect("ware reference validated above"),
+                                *v,
+                            )
+                        })
+                        .collect(),
+                    cycle: recipe.cycle,
+                    labor: recipe
+                        .labor
+                        .amounts
+                        .iter()
+                        .map(|(k, v)| {
+                            (
+                                population_class_index
+                                    .index_of(k)
+                                    .expect("population class reference validated above"),
+                                *v,
+                            )
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            wares,
+            buildings,
+            population_classes,
+            recipes,
+            ware_index,
+            building_index,
+            population_class_index,
+            recipe_index,
+            resolved_recipes,
+        })
+    }
+
+    /// The dense index a ware's ID was interned to, if it is known.
+    pub fn ware_index(&self, id: &WareTypeId) -> Option<Index> {
+        self.ware_index.index_of(id)
+    }
+
+    /// The ware ID a dense index was interned from, if it is in range.
+    pub fn ware_id(&self, index: Index) -> Option<&WareTypeId> {
+        self.ware_index.id_of(index)
+    }
+
+    /// The dense index a building's ID was interned to, if it is known.
+    pub fn building_index(&self, id: &BuildingTypeId) -> Option<Index> {
+        self.building_index.index_of(id)
+    }
+
+    /// The building ID a dense index was interned from, if it is in range.
+    pub fn building_id(&self, index: Index) -> Option<&BuildingTypeId> {
+        self.building_index.id_of(index)
+    }
+
+    /// The dense index a population class's ID was interned to, if it is
+    /// known.
+    pub fn population_class_index(&self, id: &PopulationClassId) -> Option<Index> {
+        self.population_class_index.index_of(id)
+    }
+
+    /// The population class ID a dense index was interned from, if it is in
+    /// range.
+    pub fn population_class_id(&self, index: Index) -> Option<&PopulationClassId> {
+        self.population_class_index.id_of(index)
+    }
+
+    /// The dense index a recipe's ID was interned to, if it is known.
+    pub fn recipe_index(&self, id: &RecipeId) -> Option<Index> {
+        self.recipe_index.index_of(id)
+    }
+
+    /// The recipe ID a dense index was interned from, if it is in range.
+    pub fn recipe_id(&self, index: Index) -> Option<&RecipeId> {
+        self.recipe_index.id_of(index)
+    }
+
+    /// The resolved, index-based form of a recipe, by dense index.
+    pub fn resolved_recipe(&self, index: Index) -> Option<&ResolvedRecipe> {
+        self.resolved_recipes.get(index as usize)
+    }
+
+    /// All resolved recipes, in interned index order.
+    pub fn resolved_recipes(&self) -> &[ResolvedRecipe] {
+        &self.resolved_recipes
+    }
+
+    pub fn wares(&self) -> &BTreeMap<WareTypeId, WareType> {
+        &self.wares
+    }
+
+    pub fn buildings(&self) -> &BTreeMap<BuildingTypeId, BuildingType> {
+        &self.buildings
+    }
+
+    pub fn population_classes(&self) -> &BTreeMap<PopulationClassId, PopulationClass> {
+        &self.population_classes
+    }
+
+    pub fn recipes(&self) -> &BTreeMap<RecipeId, Recipe> {
+        &self.recipes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::sim::model::{Balance, Metadata, PopulationClassId};
+
+    fn ware() -> WareType {
+        WareType {
+            metadata: Metadata::default(),
+        }
+    }
+
+    fn building() -> BuildingType {
+        BuildingType {
+            metadata: Metadata::default(),
+        }
+    }
+
+    fn population_class() -> PopulationClass {
+        PopulationClass {
+            metadata: Metadata::default(),
+        }
+    }
+
+    fn recipe(building: &str, conversion: &[(&str, i64)], labor: &[(&str, i64)]) -> Recipe {
+        Recipe {
+            metadata: Metadata::default(),
+            building: BuildingTypeId::from(building),
+            conversion: Balance {
+                amounts: conversion
+                    .iter()
+                    .map(|(k, v)| (WareTypeId::from(*k), *v))
+                    .collect(),
+            },
+            cycle: Duration::from_secs(1),
+            labor: Balance {
+                amounts: labor
+                    .iter()
+                    .map(|(k, v)| (PopulationClassId::from(*k), *v))
+                    .collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn dangling_references_across_multiple_recipes_are_all_collected() {
+        let wares = BTreeMap::from([(WareTypeId::from("wood"), ware())]);
+        let buildings = BTreeMap::from([(BuildingTypeId::from("sawmill"), building())]);
+        let population_classes = BTreeMap::new();
+        let recipes = BTreeMap::from([
+            (
+                RecipeId::from("plank"),
+                recipe("sawmill", &[("wood", -1), ("plank", 1)], &[("worker", -1)]),
+            ),
+            (
+                RecipeId::from("smelt"),
+                recipe("furnace", &[("ore", -1), ("ingot", 1)], &[]),
+            ),
+        ]);
+
+        let err = Registry::build(wares, buildings, population_classes, recipes).unwrap_err();
+
+        assert_eq!(
+            err.dangling,
+            vec![
+                DanglingReference::Ware {
+                    recipe: RecipeId::from("plank"),
+                    ware: WareTypeId::from("plank"),
+                },
+                DanglingReference::PopulationClass {
+                    recipe: RecipeId::from("plank"),
+                    population_class: PopulationClassId::from("worker"),
+                },
+                DanglingReference::Building {
+                    recipe: RecipeId::from("smelt"),
+                    building: BuildingTypeId::from("furnace"),
+                },
+                DanglingReference::Ware {
+                    recipe: RecipeId::from("smelt"),
+                    ware: WareTypeId::from("ingot"),
+                },
+                DanglingReference::Ware {
+                    recipe: RecipeId::from("smelt"),
+                    ware: WareTypeId::from("ore"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn valid_config_interns_to_stable_indices_and_resolves_correctly() {
+        let wares = BTreeMap::from([
+            (WareTypeId::from("wood"), ware()),
+            (WareTypeId::from("plank"), ware()),
+        ]);
+        let buildings = BTreeMap::from([(BuildingTypeId::from("sawmill"), building())]);
+        let population_classes =
+            BTreeMap::from([(PopulationClassId::from("worker"), population_class())]);
+        let recipes = BTreeMap::from([(
+            RecipeId::from("plank"),
+            recipe("sawmill", &[("wood", -2), ("plank", 1)], &[("worker", -1)]),
+        )]);
+
+        let registry =
+            Registry::build(wares, buildings, population_classes, recipes).expect("valid config");
+
+        let recipe_index = registry
+            .recipe_index(&RecipeId::from("plank"))
+            .expect("recipe interned");
+        let resolved = registry
+            .resolved_recipe(recipe_index)
+            .expect("resolved recipe present");
+
+        let wood_index = registry.ware_index(&WareTypeId::from("wood")).unwrap();
+        let plank_index = registry.ware_index(&WareTypeId::from("plank")).unwrap();
+        let sawmill_index = registry
+            .building_index(&BuildingTypeId::from("sawmill"))
+            .unwrap();
+        let worker_index = registry
+            .population_class_index(&PopulationClassId::from("worker"))
+            .unwrap();
+
+        assert_eq!(resolved.building, sawmill_index);
+        assert_eq!(resolved.conversion[&wood_index], -2);
+        assert_eq!(resolved.conversion[&plank_index], 1);
+        assert_eq!(resolved.labor[&worker_index], -1);
+    }
+
+    #[test]
+    fn ware_index_and_ware_id_round_trip() {
+        let wares = BTreeMap::from([
+            (WareTypeId::from("wood"), ware()),
+            (WareTypeId::from("plank"), ware()),
+        ]);
+
+        let registry = Registry::build(wares, BTreeMap::new(), BTreeMap::new(), BTreeMap::new())
+            .expect("valid config");
+
+        let wood_id = WareTypeId::from("wood");
+        let index = registry.ware_index(&wood_id).expect("wood is interned");
+        assert_eq!(registry.ware_id(index), Some(&wood_id));
+    }
+}
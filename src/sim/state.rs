@@ -0,0 +1,170 @@
+//! Mutable simulation state, with versioned save/load snapshots.
+//!
+//! [`super::model`] and [`super::registry`] describe the *rules* of an
+//! economy: what wares, buildings, population classes, and recipes exist,
+//! and how they reference one another. Neither holds any notion of a
+//! running simulation. [`SimState`] is that missing piece: the per-building
+//! inventories, population counts, and in-progress recipe timers that
+//! change every tick. The whole thing serializes to a single [`SaveGame`]
+//! and back, so a simulation can be persisted and resumed deterministically.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::model::{Balance, PopulationClassId, RecipeId, WareTypeId};
+
+/// Identifies a single building instance within a running simulation.
+///
+/// This is distinct from [`super::model::BuildingTypeId`], which names a
+/// *kind* of building in the config; a `BuildingId` names one placed
+/// instance of that kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BuildingId(pub u32);
+
+/// A recipe in progress at a building, counting down to completion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeTimer {
+    pub recipe: RecipeId,
+    pub remaining: Duration,
+}
+
+/// The full mutable state of a running simulation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimState {
+    /// Each building's current ware inventory.
+    pub inventories: BTreeMap<BuildingId, Balance<WareTypeId>>,
+    /// Current population counts by class.
+    pub population: BTreeMap<PopulationClassId, u64>,
+    /// Recipes currently running at a building, keyed by that building.
+    pub recipe_timers: BTreeMap<BuildingId, RecipeTimer>,
+}
+
+impl SimState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Captures the current state as a versioned, serializable [`SaveGame`].
+    pub fn snapshot(&self) -> SaveGame {
+        SaveGame {
+            version: SaveGame::CURRENT_VERSION,
+            state: self.clone(),
+        }
+    }
+
+    /// Restores a [`SimState`] from a [`SaveGame`], migrating it forward to
+    /// the current version first.
+    pub fn restore(save: SaveGame) -> Result<Self, SaveGameError> {
+        save.migrate()
+    }
+}
+
+/// A versioned, serializable snapshot of a [`SimState`].
+///
+/// The version tag lets saves written by older versions of this crate be
+/// migrated forward instead of silently misread.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub state: SimState,
+}
+
+impl SaveGame {
+    /// The save format version produced by this version of the crate.
+    pub const CURRENT_VERSION: u32 = 1;
+
+    /// Migrates this save forward to [`SaveGame::CURRENT_VERSION`], applying
+    /// each version's migration in turn.
+    ///
+    /// There is only one version so far, so this either passes the state
+    /// through unchanged or rejects a save tagged with any other version. Future migrations should be added as additional
+    /// match arms here, each transforming the state from one version to the
+    /// next.
+    fn migrate(self) -> Result<SimState, SaveGameError> {
+        match self.version {
+            1 => Ok(self.state),
+            version => Err(SaveGameError::UnsupportedVersion {
+                found: version,
+                supported: Self::CURRENT_VERSION,
+            }),
+        }
+    }
+}
+
+/// An error restoring a [`SaveGame`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveGameError {
+    /// The save's version is not one this crate knows how to migrate.
+    UnsupportedVersion { found: u32, supported: u32 },
+}
+
+impl fmt::Display for SaveGameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveGameError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "save version {found} is not supported (supported version is {supported})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveGameError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated_state() -> SimState {
+        let mut state = SimState::new();
+        state.inventories.insert(
+            BuildingId(1),
+            Balance {
+                amounts: [(WareTypeId::from("wood"), 5)].into(),
+            },
+        );
+        state
+            .population
+            .insert(PopulationClassId::from("worker"), 10);
+        state.recipe_timers.insert(
+            BuildingId(1),
+            RecipeTimer {
+                recipe: RecipeId::from("sawmill"),
+                remaining: Duration::from_secs(3),
+            },
+        );
+        state
+    }
+
+    #[test]
+    fn snapshot_and_restore_round_trip_to_an_equal_state() {
+        let state = populated_state();
+
+        let save = state.snapshot();
+        let restored = SimState::restore(save).expect("current version restores");
+
+        assert_eq!(restored, state);
+    }
+
+    #[test]
+    fn restore_rejects_a_save_from_an_unsupported_version() {
+        let save = SaveGame {
+            version: 2,
+            state: populated_state(),
+        };
+
+        let err = SimState::restore(save).unwrap_err();
+
+        assert_eq!(
+            err,
+            SaveGameError::UnsupportedVersion {
+                found: 2,
+                supported: SaveGame::CURRENT_VERSION,
+            }
+        );
+    }
+}